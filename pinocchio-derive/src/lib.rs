@@ -0,0 +1,172 @@
+//! `#[derive(Visitable)]`: generates an inherent `derived_children()` method that chains a
+//! struct's annotated branch fields into one iterator of children. It does not implement
+//! [`Visitable`](https://docs.rs/pinocchio) itself: the hand-written `Visitable` impl still
+//! defines `children()`, which can simply return `self.derived_children()`.
+//!
+//! ```ignore
+//! #[derive(Visitable)]
+//! struct Node {
+//!     #[tree(branch)]
+//!     joints: Vec<Node>,
+//!     #[tree(skip)]
+//!     name: String,
+//!     local_transform: Transform,
+//! }
+//!
+//! impl Visitable for Node {
+//!     fn children(&self) -> impl Iterator<Item = &Self> {
+//!         self.derived_children()
+//!     }
+//!     // ...
+//! }
+//! ```
+//!
+//! Fields tagged `#[tree(branch)]` are recursed into; their type must be one of `Vec<Self>`,
+//! `Box<Self>` or `Option<Box<Self>>` (the usual shape for a recursive "next" pointer, since a
+//! bare `Box<Self>` field can never actually be constructed — it has no base case; a bare
+//! `Option<Self>` is rejected too, since `Option` alone provides no indirection and so is exactly
+//! as infinitely-sized as `Self` itself). Fields tagged `#[tree(skip)]` are plain data and
+//! excluded from traversal, as is any field carrying no `#[tree(...)]` attribute at all. Any
+//! other `#[tree(...)]` argument is a
+//! compile error, rather than being silently treated as `skip`. The whole `Visitable` impl,
+//! `children()` included, is still written by hand; this derive only generates
+//! `derived_children()`, by chaining the branch fields' own iterators.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, GenericArgument, Path, PathArguments, Type};
+
+/// Which container shape a `#[tree(branch)]` field has, and therefore how to turn `&self.field`
+/// into an `Iterator<Item = &Self>`.
+enum BranchShape {
+    /// `Vec<Self>`
+    Vec,
+    /// `Box<Self>`
+    Box,
+    /// `Option<Box<Self>>`
+    OptionBox,
+}
+
+/// The single generic argument of a one-parameter generic type, e.g. `Self` out of `Box<Self>`.
+fn generic_argument(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let PathArguments::AngleBracketed(args) = &type_path.path.segments.last()?.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    })
+}
+
+/// Whether a type's outer path segment is `Box`.
+fn is_box(ty: &Type) -> bool {
+    matches!(ty, Type::Path(type_path) if type_path.path.segments.last().is_some_and(|s| s.ident == "Box"))
+}
+
+/// Recognizes `Vec<_>`, `Box<_>` and `Option<Box<_>>` by their path segments, without inspecting
+/// whether the innermost type is actually `Self` — the field is trusted to wrap `Self` as the
+/// request describes, exactly like a hand-written `children()` would trust it. A bare `Option<_>`
+/// is not a recognized shape: unlike `Box`, `Option` provides no indirection, so `Option<Self>`
+/// would be exactly as infinitely-sized as `Self` itself.
+fn branch_shape(ty: &Type) -> Option<BranchShape> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let Path { segments, .. } = &type_path.path;
+    match segments.last()?.ident.to_string().as_str() {
+        "Vec" => Some(BranchShape::Vec),
+        "Box" => Some(BranchShape::Box),
+        "Option" if generic_argument(ty).is_some_and(is_box) => Some(BranchShape::OptionBox),
+        _ => None,
+    }
+}
+
+/// What a field's `#[tree(...)]` attribute says about it.
+enum TreeArg {
+    Branch,
+    Skip,
+}
+
+/// Reads a field's `#[tree(...)]` attribute, if any. `Ok(None)` means the field carries no such
+/// attribute (and is therefore skipped, same as an explicit `#[tree(skip)]`). Any argument other
+/// than `branch` or `skip` is an error rather than being silently treated as `skip`.
+fn tree_arg(attrs: &[Attribute]) -> syn::Result<Option<TreeArg>> {
+    for attr in attrs {
+        if !attr.path().is_ident("tree") {
+            continue;
+        }
+        let path = attr.parse_args::<Path>()?;
+        if path.is_ident("branch") {
+            return Ok(Some(TreeArg::Branch));
+        } else if path.is_ident("skip") {
+            return Ok(Some(TreeArg::Skip));
+        }
+        return Err(syn::Error::new_spanned(
+            &path,
+            "expected `#[tree(branch)]` or `#[tree(skip)]`",
+        ));
+    }
+    Ok(None)
+}
+
+#[proc_macro_derive(Visitable, attributes(tree))]
+pub fn derive_visitable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let Data::Struct(data) = &input.data else {
+        return syn::Error::new_spanned(&input, "#[derive(Visitable)] only supports structs")
+            .to_compile_error()
+            .into();
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return syn::Error::new_spanned(&input, "#[derive(Visitable)] requires named fields")
+            .to_compile_error()
+            .into();
+    };
+
+    let mut branches = Vec::new();
+    let mut errors = Vec::new();
+
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("named field");
+        match tree_arg(&field.attrs) {
+            Ok(Some(TreeArg::Branch)) => match branch_shape(&field.ty) {
+                Some(BranchShape::Vec) => branches.push(quote! { self.#ident.iter() }),
+                Some(BranchShape::Box) => {
+                    branches.push(quote! { ::std::iter::once(::std::convert::AsRef::as_ref(&self.#ident)) })
+                }
+                Some(BranchShape::OptionBox) => branches.push(
+                    quote! { self.#ident.iter().map(|boxed| ::std::convert::AsRef::as_ref(boxed)) },
+                ),
+                None => {
+                    let message = format!(
+                        "field `{ident}` is marked #[tree(branch)] but is not a Vec<Self>, Box<Self> \
+                         or Option<Box<Self>>"
+                    );
+                    errors.push(quote! { compile_error!(#message); });
+                }
+            },
+            Ok(Some(TreeArg::Skip)) | Ok(None) => {}
+            Err(err) => errors.push(err.to_compile_error()),
+        }
+    }
+
+    let expanded = quote! {
+        #(#errors)*
+
+        impl #impl_generics #name #type_generics #where_clause {
+            /// Chains the `#[tree(branch)]` fields into a single iterator, generated by
+            /// `#[derive(Visitable)]`. Call this from your hand-written `children()`.
+            pub fn derived_children(&self) -> impl Iterator<Item = &Self> {
+                ::std::iter::empty() #( .chain(#branches) )*
+            }
+        }
+    };
+
+    expanded.into()
+}
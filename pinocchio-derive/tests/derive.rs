@@ -0,0 +1,66 @@
+//! `#[derive(Visitable)]` only generates an inherent method (see the `pinocchio_derive` crate
+//! docs), so it must be exercised from an integration test rather than `#[cfg(test)]` inside the
+//! proc-macro crate itself, which cannot invoke its own derive macro.
+
+use pinocchio_derive::Visitable;
+
+#[derive(Visitable)]
+struct VecNode {
+    #[tree(branch)]
+    children: Vec<VecNode>,
+    #[tree(skip)]
+    name: String,
+    val: i32,
+}
+
+#[test]
+fn derives_children_from_vec_branch() {
+    let tree = VecNode {
+        children: vec![
+            VecNode { children: vec![], name: "a".into(), val: 2 },
+            VecNode { children: vec![], name: "b".into(), val: 3 },
+        ],
+        name: "root".into(),
+        val: 1,
+    };
+
+    let children: Vec<i32> = tree.derived_children().map(|node| node.val).collect();
+    assert_eq!(children, vec![2, 3]);
+}
+
+#[derive(Visitable)]
+struct LinkedNode {
+    #[tree(branch)]
+    next: Option<Box<LinkedNode>>,
+    val: i32,
+}
+
+#[test]
+fn derives_children_from_optional_box_branch() {
+    // the realistic recursive-pointer idiom: `Option` supplies the base case a bare `Box<Self>`
+    // field cannot (such a field could never be constructed, having no terminating value).
+    let tail = LinkedNode { next: None, val: 3 };
+    let mid = LinkedNode { next: Some(Box::new(tail)), val: 2 };
+    let head = LinkedNode { next: Some(Box::new(mid)), val: 1 };
+
+    let children: Vec<i32> = head.derived_children().map(|node| node.val).collect();
+    assert_eq!(children, vec![2]);
+
+    let grandchildren: Vec<i32> =
+        head.derived_children().flat_map(|node| node.derived_children()).map(|node| node.val).collect();
+    assert_eq!(grandchildren, vec![3]);
+}
+
+#[derive(Visitable)]
+struct UnboundedNode {
+    #[tree(branch)]
+    next: Box<UnboundedNode>,
+}
+
+// a bare `Box<Self>` field has no base case and so can never actually be constructed (unlike
+// `Option<Box<Self>>`, see `derives_children_from_optional_box_branch` above); this only checks
+// that the generated `derived_children` type-checks for that shape.
+#[allow(dead_code)]
+fn assert_bare_box_branch_compiles(node: &UnboundedNode) -> impl Iterator<Item = &UnboundedNode> {
+    node.derived_children()
+}
@@ -5,8 +5,14 @@
 //! * Methods – imperative forms with the exception of getters and factories, which
 //!             are uses substantives (i.e., omit a `get_` prefix) much like the standard library.
 //!             Callback methods have a `on_` prefix
+//!
+//! The companion `pinocchio-derive` crate provides `#[derive(Visitable)]`, which generates an
+//! inherent `derived_children()` method from fields annotated `#[tree(branch)]`; a hand-written
+//! [`Visitable::children`] can return it.
 
 pub mod visitor;
 
-pub use visitor::visitable::{Accumulable, Visitable};
+pub use visitor::paths::{PathIter, PathSnapshot};
+pub use visitor::visitable::{Accumulable, Traversal, Visitable};
+pub use visitor::visitable_mut::VisitableMut;
 pub use visitor::visiting::Visitor;
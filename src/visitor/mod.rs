@@ -0,0 +1,6 @@
+//! Depth-first tree traversal with a visitor pattern.
+
+pub mod paths;
+pub mod visitable;
+pub mod visitable_mut;
+pub mod visiting;
@@ -0,0 +1,258 @@
+//! An [`Iterator`] adapter over [`Visitor`], so traversals can be composed with the standard
+//! adapter ecosystem (`map`, `filter`, `collect`, `zip`, ...) instead of hand-rolled `while let`
+//! loops.
+
+use super::visitable::{Accumulable, Traversal, Visiting};
+use super::visiting::Visitor;
+
+/// An owned, cloned snapshot of the path from the root to the node currently being visited.
+pub type PathSnapshot<'a, T, Accumulator> = Vec<(&'a T, Accumulator)>;
+
+/// Adapts a [`Visitor`] into a standard [`Iterator`] of [`PathSnapshot`]s, yielded in the same
+/// order [`Visitor::next`] would. Built by [`super::visitable::Visitable::iter_paths`].
+#[allow(clippy::type_complexity)]
+pub struct PathIter<
+    'a,
+    T,
+    It,
+    Accumulator,
+    GetChildren,
+    Parameter,
+    Accumulate,
+    OnVisit,
+    Backward,
+    OnLeave,
+    PathSegment,
+    Segment,
+    Payload,
+> where
+    It: Iterator<Item = &'a T>,
+    GetChildren: Fn(&'a T) -> It,
+    Accumulator: Accumulable,
+    Accumulate: FnMut(&T, &Accumulator, Option<&Parameter>) -> Accumulator,
+    OnVisit: FnMut(&T, &[(&'a T, Accumulator)], &[PathSegment], &mut Payload) -> Traversal,
+    Backward: Accumulable,
+    OnLeave: FnMut(&T, &[(&'a T, Accumulator)], &Backward, &mut Payload) -> Backward,
+    Segment: Fn(&T) -> PathSegment,
+{
+    visitor: Visitor<
+        'a,
+        T,
+        It,
+        Accumulator,
+        GetChildren,
+        Parameter,
+        Accumulate,
+        OnVisit,
+        Backward,
+        OnLeave,
+        PathSegment,
+        Segment,
+        Payload,
+    >,
+    zipped: Box<dyn Iterator<Item = &'a Parameter> + 'a>,
+    payload: &'a mut Payload,
+}
+
+impl<
+        'a,
+        T,
+        It,
+        Accumulator,
+        GetChildren,
+        Parameter,
+        Accumulate,
+        OnVisit,
+        Backward,
+        OnLeave,
+        PathSegment,
+        Segment,
+        Payload,
+    >
+    PathIter<
+        'a,
+        T,
+        It,
+        Accumulator,
+        GetChildren,
+        Parameter,
+        Accumulate,
+        OnVisit,
+        Backward,
+        OnLeave,
+        PathSegment,
+        Segment,
+        Payload,
+    >
+where
+    It: Iterator<Item = &'a T>,
+    GetChildren: Fn(&'a T) -> It,
+    Accumulator: Accumulable,
+    Accumulate: FnMut(&T, &Accumulator, Option<&Parameter>) -> Accumulator,
+    OnVisit: FnMut(&T, &[(&'a T, Accumulator)], &[PathSegment], &mut Payload) -> Traversal,
+    Backward: Accumulable,
+    OnLeave: FnMut(&T, &[(&'a T, Accumulator)], &Backward, &mut Payload) -> Backward,
+    Segment: Fn(&T) -> PathSegment,
+{
+    /// Wraps a [`Visitor`], together with the per-node parameter iterator and payload it needs
+    /// at each step, into a standard [`Iterator`] of [`PathSnapshot`]s.
+    pub fn new(
+        visitor: Visitor<
+            'a,
+            T,
+            It,
+            Accumulator,
+            GetChildren,
+            Parameter,
+            Accumulate,
+            OnVisit,
+            Backward,
+            OnLeave,
+            PathSegment,
+            Segment,
+            Payload,
+        >,
+        zipped: impl Iterator<Item = &'a Parameter> + 'a,
+        payload: &'a mut Payload,
+    ) -> Self {
+        Self {
+            visitor,
+            zipped: Box::new(zipped),
+            payload,
+        }
+    }
+}
+
+impl<
+        'a,
+        T,
+        It,
+        Accumulator,
+        GetChildren,
+        Parameter,
+        Accumulate,
+        OnVisit,
+        Backward,
+        OnLeave,
+        PathSegment,
+        Segment,
+        Payload,
+    > Iterator
+    for PathIter<
+        'a,
+        T,
+        It,
+        Accumulator,
+        GetChildren,
+        Parameter,
+        Accumulate,
+        OnVisit,
+        Backward,
+        OnLeave,
+        PathSegment,
+        Segment,
+        Payload,
+    >
+where
+    It: Iterator<Item = &'a T>,
+    GetChildren: Fn(&'a T) -> It,
+    Accumulator: Accumulable + Clone,
+    Accumulate: FnMut(&T, &Accumulator, Option<&Parameter>) -> Accumulator,
+    OnVisit: FnMut(&T, &[(&'a T, Accumulator)], &[PathSegment], &mut Payload) -> Traversal,
+    Backward: Accumulable,
+    OnLeave: FnMut(&T, &[(&'a T, Accumulator)], &Backward, &mut Payload) -> Backward,
+    Segment: Fn(&T) -> PathSegment,
+{
+    type Item = PathSnapshot<'a, T, Accumulator>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // the borrow returned by `Visitor::next` only needs to prove the walk advanced; the
+        // snapshot itself is read back, owned, from `visitor.stack` right after
+        self.visitor.next(self.zipped.next(), self.payload)?;
+        Some(self.visitor.stack.iter().map(|(node, acc)| (*node, acc.clone())).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::visitable::Visitable;
+    use super::*;
+
+    // `Accumulable for i32` is already implemented by `visitable::tests`, which is compiled
+    // alongside this module under `cargo test`; a second impl here would conflict.
+
+    #[test]
+    fn test_iter_paths() {
+        #[derive(Debug)]
+        struct Node {
+            pub val: i32,
+            pub children: Vec<Node>,
+        }
+        impl Node {
+            fn new(val: i32, children: Vec<Node>) -> Self {
+                Self { val, children }
+            }
+        }
+
+        impl Visitable for Node {
+            type Accumulator = i32;
+            type Parameter = i32;
+            type Payload = ();
+            type Backward = i32;
+            type PathSegment = i32;
+
+            fn children(&self) -> impl Iterator<Item = &Self> {
+                self.children.iter()
+            }
+
+            fn accumulate(&self, acc: &Self::Accumulator, zipped: Option<&Self::Parameter>) -> Self::Accumulator {
+                self.val * zipped.unwrap_or(&1) + acc
+            }
+
+            fn segment(&self) -> Self::PathSegment {
+                self.val
+            }
+
+            fn on_visit(
+                &self,
+                _stack: &[(&Self, Self::Accumulator)],
+                _path: &[Self::PathSegment],
+                _payload: &mut Self::Payload,
+            ) -> Traversal {
+                Traversal::Continue
+            }
+
+            fn on_leave(
+                &self,
+                _stack: &[(&Self, Self::Accumulator)],
+                children: &Self::Backward,
+                _payload: &mut Self::Payload,
+            ) -> Self::Backward {
+                self.val + children
+            }
+        }
+
+        // Simple tree: root node with two children, one of which has its own child
+        let tree = Node::new(
+            1,
+            vec![Node::new(2, vec![Node::new(4, vec![])]), Node::new(3, vec![])],
+        );
+
+        let parameters = [1, 1, 1, 1];
+        let mut payload = ();
+
+        let leaves: Vec<_> = tree
+            .iter_paths(2, parameters.iter(), &mut payload)
+            .filter(|path| path.len() == 2)
+            .collect();
+
+        // two paths of length 2 (depth 1): root -> 2 and root -> 3; root -> 2 -> 4 is filtered out
+        assert_eq!(leaves.len(), 2);
+        let values: Vec<Vec<i32>> = leaves
+            .iter()
+            .map(|path| path.iter().map(|(node, _)| node.val).collect())
+            .collect();
+        assert_eq!(values, vec![vec![1, 2], vec![1, 3]]);
+    }
+}
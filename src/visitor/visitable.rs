@@ -1,6 +1,7 @@
 //! Interfaces for a depth-first tree traversal
 //! with a visitor pattern.
 
+use super::paths::{PathIter, PathSnapshot};
 use super::visiting::Visitor;
 
 /// Values that can be accumulated *along a path* during tree traversal.
@@ -14,18 +15,34 @@ pub trait Accumulable {
 
 /// Trait for structs (visitors) implementing the
 /// [visitor pattern](https://en.wikipedia.org/wiki/Visitor_pattern)
-pub trait Visiting<'a, T, Parameter, Accumulator>
+pub trait Visiting<'a, T, Parameter, Accumulator, Payload>
 where
     T: 'a,
     Accumulator: Accumulable,
 {
+    /// Value folded leaf-to-root by the post-order sweep; see [`Visitable::on_leave`].
+    type Backward: Accumulable;
+
     /// Visits the next node a tree. A parameter can be provided
     /// to the computation of the accumulation for each node *along a path*.
-    ///
-    /// In animation, for instance, we want to compute the pose of a character
-    /// by applying angles to each joint. Hence, to compute the local coordinate systems
-    /// an additional parameter is required.
-    fn next(&mut self, parameter: Option<&Parameter>) -> Option<&Vec<(&T, Accumulator)>>;
+    /// `payload` is handed to nodes visited or left during this step, granting access to the
+    /// outside scope.
+    fn next(&mut self, parameter: Option<&Parameter>, payload: &mut Payload) -> Option<&Vec<(&T, Accumulator)>>;
+
+    /// The root's fully composed [`Visitable::on_leave`] result, once the traversal is complete
+    /// (`next` has returned `None`); `None` if called before the walk finished.
+    fn result(self) -> Option<Self::Backward>;
+}
+
+/// Decision returned by [`Visitable::on_visit`], controlling how the traversal proceeds past
+/// the node that was just visited.
+pub enum Traversal {
+    /// Carry on as usual: descend into this node's children.
+    Continue,
+    /// Do not descend into this node's children, but keep visiting its siblings.
+    SkipChildren,
+    /// Abort the whole traversal immediately; no further node is visited.
+    Stop,
 }
 
 /// Trait for structures that represent nodes of a tree that allows visiting its children depth first.
@@ -42,6 +59,16 @@ where
     type Parameter;
     /// While visiting each node, mutable data is passed around to grant access to the outside scope
     type Payload;
+    /// Value accumulated *up* a path during the post-order sweep performed by [`Visitable::on_leave`],
+    /// e.g. an articulated-body inertia or a joint torque composed from a node's children back
+    /// toward the root.
+    type Backward: Accumulable;
+    /// A name-addressed identifier for this node, e.g. `"shoulder"` in a chain such as
+    /// `"left_arm/shoulder/elbow"`. Unlike [`Visitable::Accumulator`] it is not combined along
+    /// the path; instead, [`Visitable::on_visit`] is handed the full slice of segments from the
+    /// root to the current node, to build name-addressed lookups or serialize results keyed by
+    /// chain path.
+    type PathSegment;
 
     /// Gets the node's children.
     fn children(&self) -> impl Iterator<Item = &Self>;
@@ -49,27 +76,91 @@ where
     /// When visiting this node, this method is called compute the accumulation along a path.
     fn accumulate(&self, acc: &Self::Accumulator, zipped: Option<&Self::Parameter>) -> Self::Accumulator;
 
+    /// This node's own [`Visitable::PathSegment`], e.g. its name.
+    fn segment(&self) -> Self::PathSegment;
+
     /// Arbitrary action when visiting a node along path. It receives a reference to the
-    /// "history", the previous nodes and accumulator values that is, in addition to mutable
-    /// data that allows interaction with the outside context.
-    fn on_visit(&self, stack: &[(&Self, Self::Accumulator)], payload: &mut Self::Payload);
+    /// "history", the previous nodes and accumulator values that is, in addition to the path
+    /// of segments from the root to this node and mutable data that allows interaction with
+    /// the outside context. The returned [`Traversal`] decides whether the walk descends into
+    /// this node's children, skips them, or stops altogether.
+    fn on_visit(
+        &self,
+        stack: &[(&Self, Self::Accumulator)],
+        path: &[Self::PathSegment],
+        payload: &mut Self::Payload,
+    ) -> Traversal;
+
+    /// Arbitrary action called once this node is fully explored, i.e. after all of its children
+    /// have themselves been left. `children` is the fold of the node's children's own `on_leave`
+    /// results, combined with [`Accumulable::accumulate`] starting from [`Accumulable::neutral`].
+    /// The value returned here is in turn folded into this node's own parent's `children`,
+    /// letting values such as articulated-body inertias or joint torques be composed
+    /// leaf-to-root in the very same walk that computes `accumulate` root-to-leaf.
+    fn on_leave(
+        &self,
+        stack: &[(&Self, Self::Accumulator)],
+        children: &Self::Backward,
+        payload: &mut Self::Payload,
+    ) -> Self::Backward;
 
     /// Generates a visitor for the tree with the current element as its root.
-    fn visitor(&self, max_depth: usize) -> impl Visiting<Self, Self::Parameter, Self::Accumulator> {
-        Visitor::new(self, max_depth, |s| s.children(), |s, a, z| s.accumulate(a, z))
+    fn visitor(
+        &self,
+        max_depth: usize,
+    ) -> impl Visiting<Self, Self::Parameter, Self::Accumulator, Self::Payload, Backward = Self::Backward> {
+        Visitor::new(
+            self,
+            max_depth,
+            |s| s.children(),
+            |s, a, z| s.accumulate(a, z),
+            |s| s.segment(),
+            |s, stack, path, payload| s.on_visit(stack, path, payload),
+            |s, stack, children, payload| s.on_leave(stack, children, payload),
+        )
     }
-    /// Visits all children and children's children and calls `accumulate` (implicitly) and `on_visit`
-    /// on each node.
+    /// Visits all children and children's children, calling `accumulate` (implicitly) and
+    /// `on_visit` on each node in pre-order, and `on_leave` on each node in post-order.
+    /// The traversal may be pruned or aborted early; see [`Traversal`]. Returns the root's
+    /// composed [`Visitable::on_leave`] result, folded leaf-to-root over the whole walk.
     fn visit<'a>(
         &'a self,
         max_depth: usize,
         mut zipped: impl Iterator<Item = &'a Self::Parameter>,
         payload: &mut Self::Payload,
-    ) {
+    ) -> Self::Backward {
         let mut visitor = self.visitor(max_depth);
-        while let Some(stack) = visitor.next(zipped.next()) {
-            self.on_visit(stack, payload);
-        }
+        while visitor.next(zipped.next(), payload).is_some() {}
+        visitor
+            .result()
+            .expect("a finished traversal always visits the root, producing a Backward value via on_leave")
+    }
+
+    /// Adapts the traversal into a standard [`Iterator`] of [`PathSnapshot`]s, so it can be
+    /// composed with `map`, `filter`, `collect`, `zip` and the rest of the adapter ecosystem
+    /// instead of a hand-rolled `while let` loop, e.g.
+    /// `tree.iter_paths(max_depth, params, payload).filter(|p| p.len() == target_depth).collect()`.
+    /// Requires [`Visitable::Accumulator`] to be [`Clone`], since each yielded snapshot owns a
+    /// copy of the accumulator at every node along the path.
+    fn iter_paths<'a>(
+        &'a self,
+        max_depth: usize,
+        zipped: impl Iterator<Item = &'a Self::Parameter> + 'a,
+        payload: &'a mut Self::Payload,
+    ) -> impl Iterator<Item = PathSnapshot<'a, Self, Self::Accumulator>> + 'a
+    where
+        Self::Accumulator: Clone,
+    {
+        let visitor = Visitor::new(
+            self,
+            max_depth,
+            |s| s.children(),
+            |s, a, z| s.accumulate(a, z),
+            |s| s.segment(),
+            |s, stack, path, payload| s.on_visit(stack, path, payload),
+            |s, stack, children, payload| s.on_leave(stack, children, payload),
+        );
+        PathIter::new(visitor, zipped, payload)
     }
 }
 
@@ -111,6 +202,10 @@ mod tests {
 
             type Payload = std::slice::Iter<'static, i32>;
 
+            type Backward = i32;
+
+            type PathSegment = i32;
+
             fn children(&self) -> impl Iterator<Item = &Self> {
                 self.children.iter()
             }
@@ -119,8 +214,19 @@ mod tests {
                 self.val * zipped.unwrap_or(&1) + acc
             }
 
-            fn on_visit(&self, stack: &[(&Self, Self::Accumulator)], payload: &mut Self::Payload) {
+            fn segment(&self) -> Self::PathSegment {
+                self.val
+            }
+
+            fn on_visit(
+                &self,
+                stack: &[(&Self, Self::Accumulator)],
+                path: &[Self::PathSegment],
+                payload: &mut Self::Payload,
+            ) -> Traversal {
                 let depth = stack.len();
+                assert_eq!(depth, path.len());
+                assert_eq!(path.last(), Some(&self.val));
                 let (_, acc) = stack.last().unwrap(); // guaranteed to bot be empty
                 let expectation = payload.next().unwrap();
                 println!(
@@ -128,6 +234,17 @@ mod tests {
                     self.val, acc
                 );
                 assert!(expectation == acc);
+                Traversal::Continue
+            }
+
+            fn on_leave(
+                &self,
+                _stack: &[(&Self, Self::Accumulator)],
+                children: &Self::Backward,
+                _payload: &mut Self::Payload,
+            ) -> Self::Backward {
+                // the node's own contribution, folded with whatever its children contributed
+                self.val + children
             }
         }
 
@@ -140,6 +257,81 @@ mod tests {
         // Parameter vector for the nodes
         let parameters = [2, 3, 4].iter();
 
-        tree.visit(2, parameters, &mut expectation);
+        // root's on_leave value, folded leaf-to-root: (2 + 3) combined into 1's own contribution
+        let result = tree.visit(2, parameters, &mut expectation);
+        assert_eq!(result, 6);
+    }
+
+    #[test]
+    fn test_stop_traversal() {
+        // aborting the walk via `Traversal::Stop` must not panic, and `on_leave` must still run
+        // for the stopped node and every ancestor still on the stack, folding whatever was
+        // accumulated before the abort.
+        #[derive(Debug)]
+        struct Node {
+            pub val: i32,
+            pub children: Vec<Node>,
+        }
+        impl Node {
+            fn new(val: i32, children: Vec<Node>) -> Self {
+                Self { val, children }
+            }
+        }
+
+        impl Visitable for Node {
+            type Accumulator = i32;
+            type Parameter = ();
+            type Payload = ();
+            type Backward = i32;
+            type PathSegment = i32;
+
+            fn children(&self) -> impl Iterator<Item = &Self> {
+                self.children.iter()
+            }
+
+            fn accumulate(&self, acc: &Self::Accumulator, _zipped: Option<&Self::Parameter>) -> Self::Accumulator {
+                self.val + acc
+            }
+
+            fn segment(&self) -> Self::PathSegment {
+                self.val
+            }
+
+            fn on_visit(
+                &self,
+                _stack: &[(&Self, Self::Accumulator)],
+                _path: &[Self::PathSegment],
+                _payload: &mut Self::Payload,
+            ) -> Traversal {
+                // stop as soon as node 3 is reached: its own child (5) must never be visited
+                if self.val == 3 {
+                    Traversal::Stop
+                } else {
+                    Traversal::Continue
+                }
+            }
+
+            fn on_leave(
+                &self,
+                _stack: &[(&Self, Self::Accumulator)],
+                children: &Self::Backward,
+                _payload: &mut Self::Payload,
+            ) -> Self::Backward {
+                self.val + children
+            }
+        }
+
+        // root(1) -> [2 -> [4], 3 -> [5]]; node 3 stops the walk, so node 5 is never visited
+        let tree = Node::new(
+            1,
+            vec![
+                Node::new(2, vec![Node::new(4, vec![])]),
+                Node::new(3, vec![Node::new(5, vec![])]),
+            ],
+        );
+
+        // 1 + (2 + 4) + (3 + 0): node 5 contributes nothing since it was never reached
+        let result = tree.visit(2, std::iter::empty(), &mut ());
+        assert_eq!(result, 10);
     }
 }
@@ -0,0 +1,178 @@
+//! Interfaces for a depth-first tree traversal that mutates the tree in place.
+
+use super::visitable::Accumulable;
+
+/// Mutable counterpart of [`super::visitable::Visitable`]. Setting joint states, caching a
+/// computed local frame on each node, or normalizing a quaternion after integration all
+/// require writing back into the tree while it is being walked, which the read-only
+/// `children`/`on_visit` of `Visitable` cannot do.
+///
+/// `VisitableMut` is narrower than `Visitable`: there is no post-order `on_leave`, no
+/// data-dependent `Traversal` and no path segments here, only a pre-order walk with in-place
+/// writes. The walk is driven recursively rather than through an explicit stack of iterators:
+/// each recursive call owns exactly one `&mut Self`, which is what lets it split off its
+/// children's mutable borrows one at a time while still holding on to its own.
+pub trait VisitableMut
+where
+    Self: Sized,
+{
+    /// Accumulator type, see [`super::visitable::Visitable::Accumulator`].
+    type Accumulator: Accumulable;
+    /// Parameter type, see [`super::visitable::Visitable::Parameter`].
+    type Parameter;
+    /// Payload type, see [`super::visitable::Visitable::Payload`].
+    type Payload;
+
+    /// Gets the node's children, with mutable access.
+    fn children_mut(&mut self) -> impl Iterator<Item = &mut Self>;
+
+    /// When visiting this node, this method is called to compute the accumulation along a path.
+    fn accumulate(&self, acc: &Self::Accumulator, zipped: Option<&Self::Parameter>) -> Self::Accumulator;
+
+    /// Arbitrary action when visiting a node along a path, with mutable access to the node
+    /// itself so it can write back results.
+    fn on_visit_mut(&mut self, acc: &Self::Accumulator, payload: &mut Self::Payload);
+
+    /// Visits all children and children's children in place, calling `accumulate` (implicitly)
+    /// and `on_visit_mut` on each node.
+    ///
+    /// Unlike [`super::visitable::Visitable::visit`], where `max_depth` is only a capacity hint
+    /// for the traversal's internal allocations and never bounds the walk, here `max_depth` is a
+    /// hard recursion limit: nodes deeper than `max_depth` below `self` are never visited. This
+    /// divergence is a direct consequence of `VisitableMut` being driven by real recursion rather
+    /// than an explicit stack (see the trait docs above) — there is no stack whose capacity could
+    /// be pre-allocated, so `max_depth` is repurposed as the thing recursion actually needs: a
+    /// base case.
+    fn visit_mut<'a>(
+        &'a mut self,
+        max_depth: usize,
+        mut zipped: impl Iterator<Item = &'a Self::Parameter>,
+        payload: &mut Self::Payload,
+    ) {
+        self.visit_mut_from(max_depth, &Self::Accumulator::neutral(), &mut zipped, payload);
+    }
+
+    /// Recursive worker behind [`VisitableMut::visit_mut`]: visits `self`, then its children
+    /// down to `max_depth`, threading `zipped` and `payload` through in traversal order.
+    fn visit_mut_from<'a>(
+        &mut self,
+        max_depth: usize,
+        acc: &Self::Accumulator,
+        zipped: &mut impl Iterator<Item = &'a Self::Parameter>,
+        payload: &mut Self::Payload,
+    ) where
+        Self::Parameter: 'a,
+    {
+        let acc = self.accumulate(acc, zipped.next());
+        self.on_visit_mut(&acc, payload);
+
+        if max_depth == 0 {
+            return;
+        }
+        for child in self.children_mut() {
+            child.visit_mut_from(max_depth - 1, &acc, zipped, payload);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_trivial_visitable_mut() {
+        // A tree with children and an integer as placeholder for transformations
+        #[derive(Debug)]
+        struct Node {
+            pub val: i32,
+            pub children: Vec<Node>,
+        }
+        impl Node {
+            fn new(val: i32, children: Vec<Node>) -> Self {
+                Self { val, children }
+            }
+        }
+
+        impl VisitableMut for Node {
+            type Accumulator = i32;
+
+            type Parameter = i32;
+
+            type Payload = Vec<i32>;
+
+            fn children_mut(&mut self) -> impl Iterator<Item = &mut Self> {
+                self.children.iter_mut()
+            }
+
+            fn accumulate(&self, acc: &Self::Accumulator, zipped: Option<&Self::Parameter>) -> Self::Accumulator {
+                self.val * zipped.unwrap_or(&1) + acc
+            }
+
+            fn on_visit_mut(&mut self, acc: &Self::Accumulator, payload: &mut Self::Payload) {
+                self.val = *acc;
+                payload.push(*acc);
+            }
+        }
+
+        // Simple tree: root node with two children
+        let mut tree = Node::new(1, vec![Node::new(2, vec![]), Node::new(3, vec![])]);
+
+        let parameters = [2, 3, 4];
+        let mut visited = Vec::new();
+
+        tree.visit_mut(2, parameters.iter(), &mut visited);
+
+        // each node's `val` was overwritten with its own accumulator
+        assert_eq!(visited, vec![2, 8, 14]);
+        assert_eq!(tree.val, 2);
+        assert_eq!(tree.children[0].val, 8);
+        assert_eq!(tree.children[1].val, 14);
+    }
+
+    #[test]
+    fn test_max_depth_truncates_the_walk() {
+        // unlike `Visitable::visit`, `max_depth` here is a hard recursion limit: a 4-level-deep
+        // tree walked with `max_depth` 1 must only visit the root and its direct children.
+        #[derive(Debug)]
+        struct Node {
+            pub val: i32,
+            pub visited: bool,
+            pub children: Vec<Node>,
+        }
+        impl Node {
+            fn new(val: i32, children: Vec<Node>) -> Self {
+                Self { val, visited: false, children }
+            }
+        }
+
+        impl VisitableMut for Node {
+            type Accumulator = i32;
+            type Parameter = ();
+            type Payload = ();
+
+            fn children_mut(&mut self) -> impl Iterator<Item = &mut Self> {
+                self.children.iter_mut()
+            }
+
+            fn accumulate(&self, acc: &Self::Accumulator, _zipped: Option<&Self::Parameter>) -> Self::Accumulator {
+                self.val + acc
+            }
+
+            fn on_visit_mut(&mut self, _acc: &Self::Accumulator, _payload: &mut Self::Payload) {
+                self.visited = true;
+            }
+        }
+
+        // root -> child -> grandchild -> great-grandchild
+        let mut tree = Node::new(1, vec![Node::new(2, vec![Node::new(3, vec![Node::new(4, vec![])])])]);
+
+        tree.visit_mut(1, std::iter::empty(), &mut ());
+
+        assert!(tree.visited);
+        assert!(tree.children[0].visited);
+        // depth 2 is beyond `max_depth`, and is never reached
+        assert!(!tree.children[0].children[0].visited);
+        assert!(!tree.children[0].children[0].children[0].visited);
+    }
+}
@@ -1,112 +1,273 @@
 //! Internal implementation of depth-first tree traversal
 //! with a visitor pattern.
 
-use super::visitable::{Accumulable, Visiting};
+use super::visitable::{Accumulable, Traversal, Visiting};
 use std::marker::PhantomData;
 
 /// Visitor for depth-first tree traversal. Used in in [`Visitable::visitor`],
 /// one can create this object manually by defining its behavior with closures.
-pub struct Visitor<'a, T, It, Accumulator, GetChildren, Parameter, Accumulate>
-where
+#[allow(clippy::type_complexity)]
+pub struct Visitor<
+    'a,
+    T,
+    It,
+    Accumulator,
+    GetChildren,
+    Parameter,
+    Accumulate,
+    OnVisit,
+    Backward,
+    OnLeave,
+    PathSegment,
+    Segment,
+    Payload,
+> where
     It: Iterator<Item = &'a T>,
     GetChildren: Fn(&'a T) -> It,
     Accumulator: Accumulable,
     Accumulate: FnMut(&T, &Accumulator, Option<&Parameter>) -> Accumulator,
+    OnVisit: FnMut(&T, &[(&'a T, Accumulator)], &[PathSegment], &mut Payload) -> Traversal,
+    Backward: Accumulable,
+    OnLeave: FnMut(&T, &[(&'a T, Accumulator)], &Backward, &mut Payload) -> Backward,
+    Segment: Fn(&T) -> PathSegment,
 {
     /// First element of the tree
     root: &'a T,
     /// Nodes and accumulated data of the current Path.
     /// TODO remove the iterator
     pub stack: Vec<(&'a T, Accumulator)>,
-    children: Vec<It>,
+    /// `None` marks a node whose children were pruned via [`Traversal::SkipChildren`] or that
+    /// returned [`Traversal::Stop`]: it is treated as already exhausted without ever calling
+    /// `get_children`.
+    children: Vec<Option<It>>,
+    /// For each node currently on `stack`, the fold (via [`Accumulable::accumulate`]) of the
+    /// `on_leave` results of the children visited so far. Mirrors `stack` in depth.
+    backward: Vec<Backward>,
+    /// The root's own `on_leave` result, set once the whole tree has been traversed.
+    pub result: Option<Backward>,
+    /// The path segments of the nodes currently on `stack`, from the root to the current node.
+    pub path: Vec<PathSegment>,
+    /// Set once a node's `on_visit` returns [`Traversal::Stop`]. From then on every remaining
+    /// level of `stack` is treated as already exhausted (no further node is visited), but each
+    /// is still popped through `on_leave` like a normal post-order unwind, so `result` is always
+    /// populated once the traversal is complete, stopped early or not.
+    stopped: bool,
     /// Get children of currently visited node
     pub get_children: GetChildren,
     /// Accumulate data while traversing
     pub accumulate: Accumulate,
+    /// Called when a node is visited (pre-order); decides whether to descend into it
+    pub on_visit: OnVisit,
+    /// Called once a node and all of its children have been fully explored (post-order)
+    pub on_leave: OnLeave,
+    /// Computes a node's own path segment
+    pub segment: Segment,
     /// Placeholder
-    zipped: PhantomData<Parameter>, // payload: PhantomData<Payload>,
+    zipped: PhantomData<Parameter>,
+    payload: PhantomData<Payload>,
 }
 
-impl<'a, T, It, Accumulator, GetChildren, Parameter, Accumulate>
-    Visitor<'a, T, It, Accumulator, GetChildren, Parameter, Accumulate>
+impl<
+        'a,
+        T,
+        It,
+        Accumulator,
+        GetChildren,
+        Parameter,
+        Accumulate,
+        OnVisit,
+        Backward,
+        OnLeave,
+        PathSegment,
+        Segment,
+        Payload,
+    >
+    Visitor<
+        'a,
+        T,
+        It,
+        Accumulator,
+        GetChildren,
+        Parameter,
+        Accumulate,
+        OnVisit,
+        Backward,
+        OnLeave,
+        PathSegment,
+        Segment,
+        Payload,
+    >
 where
     It: Iterator<Item = &'a T>,
     Accumulator: Accumulable,
     GetChildren: Fn(&'a T) -> It,
     Accumulate: FnMut(&T, &Accumulator, Option<&Parameter>) -> Accumulator,
+    OnVisit: FnMut(&T, &[(&'a T, Accumulator)], &[PathSegment], &mut Payload) -> Traversal,
+    Backward: Accumulable,
+    OnLeave: FnMut(&T, &[(&'a T, Accumulator)], &Backward, &mut Payload) -> Backward,
+    Segment: Fn(&T) -> PathSegment,
 {
     /// Create new visitor for a root node
     ///
     /// ## Arguments
     ///
     /// `root` – The root of the tree
-    /// `max_depth` – The max depth of the tree for allocating memory
+    /// `max_depth` – A capacity hint for the traversal's internal stacks; it does not bound the
+    ///   walk itself, which always visits the whole tree regardless of its value. Contrast
+    ///   [`super::visitable_mut::VisitableMut::visit_mut`], whose `max_depth` of the same name
+    ///   *is* enforced as a hard recursion limit.
     /// `get_children` – Closure to get children of a node
     /// `accumulate` – Closure to accumulate values on traversal
-    /// `on_visit` – Closure to be executed when a node is visited
+    /// `segment` – Closure computing a node's own path segment
+    /// `on_visit` – Closure executed when a node is visited, deciding how the walk proceeds
+    /// `on_leave` – Closure executed once a node is fully explored, folding its children's
+    /// results into this node's own contribution
     ///
     /// ## Example
     ///
     /// See unit tests for now
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         root: &'a T,
         max_depth: usize,
         get_children: GetChildren,
         accumulate: Accumulate,
-        // on_visit: OnVisit,
+        segment: Segment,
+        on_visit: OnVisit,
+        on_leave: OnLeave,
     ) -> Self {
         Self {
             root,
             stack: Vec::with_capacity(max_depth),
             children: Vec::with_capacity(max_depth),
+            backward: Vec::with_capacity(max_depth),
+            result: None,
+            path: Vec::with_capacity(max_depth),
+            stopped: false,
             get_children,
             accumulate,
-            // on_visit,
+            on_visit,
+            on_leave,
+            segment,
             zipped: PhantomData {},
+            payload: PhantomData {},
+        }
+    }
+
+    /// Pushes `node` onto `stack`/`backward`/`path`, runs `on_visit` on it, and pushes the
+    /// resulting children iterator (or `None` if the subtree is pruned or the walk is being
+    /// stopped).
+    fn enter(&mut self, node: &'a T, acc: Accumulator, payload: &mut Payload) {
+        self.stack.push((node, acc));
+        self.backward.push(Backward::neutral());
+        self.path.push((self.segment)(node));
+
+        match (self.on_visit)(node, &self.stack, &self.path, payload) {
+            Traversal::Continue => self.children.push(Some((self.get_children)(node))),
+            Traversal::SkipChildren => self.children.push(None),
+            Traversal::Stop => {
+                self.children.push(None);
+                self.stopped = true;
+            }
         }
     }
 }
 
-impl<'a, T, It, Accumulator, GetChildren, Parameter, Accumulate> Visiting<'a, T, Parameter, Accumulator>
-    for Visitor<'a, T, It, Accumulator, GetChildren, Parameter, Accumulate>
+impl<
+        'a,
+        T,
+        It,
+        Accumulator,
+        GetChildren,
+        Parameter,
+        Accumulate,
+        OnVisit,
+        Backward,
+        OnLeave,
+        PathSegment,
+        Segment,
+        Payload,
+    > Visiting<'a, T, Parameter, Accumulator, Payload>
+    for Visitor<
+        'a,
+        T,
+        It,
+        Accumulator,
+        GetChildren,
+        Parameter,
+        Accumulate,
+        OnVisit,
+        Backward,
+        OnLeave,
+        PathSegment,
+        Segment,
+        Payload,
+    >
 where
     It: Iterator<Item = &'a T>,
     Accumulator: Accumulable,
     GetChildren: Fn(&'a T) -> It,
     Accumulate: FnMut(&T, &Accumulator, Option<&Parameter>) -> Accumulator,
+    OnVisit: FnMut(&T, &[(&'a T, Accumulator)], &[PathSegment], &mut Payload) -> Traversal,
+    Backward: Accumulable,
+    OnLeave: FnMut(&T, &[(&'a T, Accumulator)], &Backward, &mut Payload) -> Backward,
+    Segment: Fn(&T) -> PathSegment,
 {
-    fn next(&mut self, zipped: Option<&Parameter>) -> Option<&Vec<(&T, Accumulator)>> {
+    type Backward = Backward;
+
+    fn next(&mut self, zipped: Option<&Parameter>, payload: &mut Payload) -> Option<&Vec<(&T, Accumulator)>> {
         if self.stack.is_empty() {
+            if self.stopped {
+                return None;
+            }
             let acc = (self.accumulate)(self.root, &Accumulator::neutral(), zipped);
-            self.stack.push((&self.root, acc));
-            self.children.push((self.get_children)(self.root));
+            self.enter(self.root, acc, payload);
             return Some(&self.stack);
         }
 
         loop {
             match self.children.last_mut() {
-                Some(current) => match current.next() {
-                    Some(next) => {
-                        let (_, acc) = self.stack.last().unwrap(); // same length
-                        let acc = (self.accumulate)(next, acc, zipped);
-
-                        self.stack.push((&next, acc));
+                Some(slot) => {
+                    // once `Stop` has been returned, every remaining level of `stack` is treated
+                    // as already exhausted: no further node is visited, but the `None` arm below
+                    // still runs `on_leave` for the stopped node and each of its ancestors, so
+                    // `self.result` is always populated by the time the stack empties out.
+                    let next = if self.stopped { None } else { slot.as_mut().and_then(Iterator::next) };
+                    match next {
+                        Some(next) => {
+                            let (_, acc) = self.stack.last().unwrap(); // same length
+                            let acc = (self.accumulate)(next, acc, zipped);
+                            self.enter(next, acc, payload);
+                            return Some(&self.stack);
+                        }
+                        None => {
+                            // this node's children are fully explored (or were skipped, or the
+                            // walk was stopped): combine their results and hand them, along with
+                            // this node, to `on_leave` right before popping it
+                            let children_result = self.backward.pop().unwrap();
+                            let (node, _) = self.stack.last().unwrap();
+                            let own = (self.on_leave)(node, &self.stack, &children_result, payload);
 
-                        let children = (self.get_children)(next);
-                        self.children.push(children);
+                            self.children.pop();
+                            self.stack.pop();
+                            self.path.pop();
 
-                        return Some(&self.stack);
-                    }
-                    None => {
-                        self.children.pop();
-                        self.stack.pop();
+                            match self.backward.last_mut() {
+                                Some(parent) => *parent = parent.accumulate(&own),
+                                None => self.result = Some(own),
+                            }
+                        }
                     }
-                },
+                }
                 None => return None,
             };
         }
     }
+
+    fn result(self) -> Option<Backward> {
+        self.result
+    }
 }
 
 #[cfg(test)]
@@ -139,6 +300,9 @@ mod tests {
             2,
             |x| x.children.iter(),
             |x, acc, zipped| acc + x.val * zipped.unwrap(),
+            |x: &Node| x.val,
+            |_node: &Node, _stack, _path, _payload: &mut ()| Traversal::Continue,
+            |node: &Node, _stack, children, _payload: &mut ()| node.val + children,
         );
 
         // Parameter vector for the nodes
@@ -147,12 +311,16 @@ mod tests {
         // To verify the correctness
         let mut expectation = [2, 8, 14].iter();
 
+        let mut payload = ();
+
         // visit the nodes
-        while let Some(stack) = visitor.next(parameter.next()) {
+        while let Some(stack) = visitor.next(parameter.next(), &mut payload) {
             let (node, acc) = stack.last().unwrap();
             let depth = stack.len();
-            println!("Node: {}, {acc}, (depth {depth})", node.val);
+            let node_val = node.val;
+            println!("Node: {node_val}, {acc}, (depth {depth})");
             assert!(expectation.next().unwrap() == acc);
+            assert_eq!(visitor.path.last(), Some(&node_val));
         }
     }
 }